@@ -3,6 +3,7 @@ mod fetch;
 use super::{ParsePlatformError, Platform};
 use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::str::FromStr;
 use thiserror::Error;
@@ -17,7 +18,30 @@ pub struct ChannelConfig {
     /// the `https://conda.anaconda.org` server but users are free to change this. This allows
     /// naming channels just by their name instead of their entire Url (e.g. "conda-forge" actually
     /// refers to "https://conda.anaconda.org/conda-forge").
-    channel_alias: Url,
+    pub channel_alias: Url,
+
+    /// A map of channel names to the Url of the server location that hosts them. This allows
+    /// referring to a custom channel server by name instead of spelling out its entire Url, e.g.
+    /// `my-channel` mapped to `https://my.server.com/conda` resolves to
+    /// `https://my.server.com/conda/my-channel`.
+    pub custom_channels: HashMap<String, Url>,
+
+    /// A map of names to a fixed set of channels. Resolving a name that is present in this map
+    /// expands to every [`Channel`] in the associated `Vec`, e.g. `defaults` commonly expands to
+    /// `main`, `r`, and `msys2`.
+    pub custom_multichannels: HashMap<String, Vec<Channel>>,
+
+    /// Channel aliases that used to be in use but have since moved to `channel_alias`. A Url
+    /// hosted under one of these aliases is re-canonicalized to live under `channel_alias`
+    /// instead.
+    pub migrated_channel_aliases: Vec<Url>,
+
+    /// A map of custom channel names to the alias they used to be hosted under. A Url that still
+    /// points at the old alias is re-canonicalized to the channel's entry in `custom_channels`,
+    /// or to `channel_alias` if the channel has no such entry.
+    /// Stored as a parsed `Url`, like `migrated_channel_aliases`, so a malformed entry fails to
+    /// deserialize loudly instead of silently never rehoming.
+    pub migrated_custom_channels: HashMap<String, Url>,
 }
 
 impl Default for ChannelConfig {
@@ -25,11 +49,79 @@ impl Default for ChannelConfig {
         ChannelConfig {
             channel_alias: Url::from_str("https://conda.anaconda.org")
                 .expect("could not parse default channel alias"),
+            custom_channels: HashMap::default(),
+            custom_multichannels: HashMap::default(),
+            migrated_channel_aliases: Vec::default(),
+            migrated_custom_channels: HashMap::default(),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Eq, PartialEq)]
+/// A partial [`ChannelConfig`] intended to be layered on top of a base configuration, e.g. system
+/// defaults overridden by a user config file, in turn overridden by CLI arguments. Follows the
+/// same override-struct pattern as Anchor's CLI config merging: every field that can be left
+/// unset uses `Option`, while collections are combined rather than replaced wholesale.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PartialChannelConfig {
+    /// Overrides [`ChannelConfig::channel_alias`] when set.
+    pub channel_alias: Option<Url>,
+
+    /// Entries merged into [`ChannelConfig::custom_channels`], overriding on key conflicts.
+    #[serde(default)]
+    pub custom_channels: HashMap<String, Url>,
+
+    /// Entries merged into [`ChannelConfig::custom_multichannels`], overriding on key conflicts.
+    #[serde(default)]
+    pub custom_multichannels: HashMap<String, Vec<Channel>>,
+
+    /// Entries appended to [`ChannelConfig::migrated_channel_aliases`].
+    #[serde(default)]
+    pub migrated_channel_aliases: Vec<Url>,
+
+    /// Entries merged into [`ChannelConfig::migrated_custom_channels`], overriding on key
+    /// conflicts.
+    #[serde(default)]
+    pub migrated_custom_channels: HashMap<String, Url>,
+}
+
+/// Layers an override config on top of a base config, with the override's values taking
+/// precedence wherever they are present.
+pub trait Merge<Rhs = Self> {
+    /// Merges `other` on top of `self` and returns the result.
+    fn merge(self, other: Rhs) -> Self;
+}
+
+impl Merge<PartialChannelConfig> for ChannelConfig {
+    fn merge(mut self, other: PartialChannelConfig) -> Self {
+        if let Some(channel_alias) = other.channel_alias {
+            self.channel_alias = channel_alias;
+        }
+        self.custom_channels.extend(other.custom_channels);
+        self.custom_multichannels.extend(other.custom_multichannels);
+        self.migrated_channel_aliases
+            .extend(other.migrated_channel_aliases);
+        self.migrated_custom_channels
+            .extend(other.migrated_custom_channels);
+        self
+    }
+}
+
+impl Merge for PartialChannelConfig {
+    fn merge(mut self, other: Self) -> Self {
+        if other.channel_alias.is_some() {
+            self.channel_alias = other.channel_alias;
+        }
+        self.custom_channels.extend(other.custom_channels);
+        self.custom_multichannels.extend(other.custom_multichannels);
+        self.migrated_channel_aliases
+            .extend(other.migrated_channel_aliases);
+        self.migrated_custom_channels
+            .extend(other.migrated_custom_channels);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
 pub struct Channel {
     /// The platforms supported by this channel, or None if no explicit platforms have been
     /// specified.
@@ -70,11 +162,38 @@ impl Channel {
         Ok(channel)
     }
 
+    /// Parses a [`Channel`] from a string and a channel configuration, expanding to several
+    /// channels if `str` names a multichannel (see [`ChannelConfig::custom_multichannels`]).
+    pub fn from_str_multi(
+        str: impl AsRef<str>,
+        config: &ChannelConfig,
+    ) -> Result<SmallVec<[Self; 1]>, ParseChannelError> {
+        let str = str.as_ref();
+        let (platforms, channel) = parse_platforms(str)?;
+
+        if parse_scheme(channel).is_none() && !is_path(channel) {
+            if let Some(channels) = config.custom_multichannels.get(channel) {
+                return Ok(channels
+                    .iter()
+                    .cloned()
+                    .map(|mut channel| {
+                        if platforms.is_some() {
+                            channel.platforms = platforms.clone();
+                        }
+                        channel
+                    })
+                    .collect());
+            }
+        }
+
+        Channel::from_str(str, config).map(|channel| SmallVec::from_buf([channel]))
+    }
+
     /// Constructs a new [`Channel`] from a `Url` and associated platforms.
     pub fn from_url(
         url: Url,
         platforms: Option<impl Into<SmallVec<[Platform; 2]>>>,
-        _config: &ChannelConfig,
+        config: &ChannelConfig,
     ) -> Self {
         let path = url.path().trim_end_matches('/');
 
@@ -88,10 +207,59 @@ impl Channel {
             };
         }
 
-        // Case 2: migrated_custom_channels
-        // Case 3: migrated_channel_aliases
-        // Case 4: custom_channels matches
-        // Case 5: channel_alias match
+        // Case 2: migrated_custom_channels: the url still points at the alias a custom channel
+        // used to be hosted under, re-home it to its current `custom_channels` entry (or, lacking
+        // that, the current `channel_alias`).
+        for (name, old_alias) in config.migrated_custom_channels.iter() {
+            if let Some(subpath) = strip_channel_prefix(&url, old_alias, name) {
+                let base = config
+                    .custom_channels
+                    .get(name)
+                    .cloned()
+                    .unwrap_or_else(|| config.channel_alias.clone());
+                return Self::from_custom_location(&base, name, subpath, platforms);
+            }
+        }
+
+        // Case 3: migrated_channel_aliases: the url is hosted under an alias that has since been
+        // replaced by `channel_alias`; re-canonicalize it before continuing.
+        let url = config
+            .migrated_channel_aliases
+            .iter()
+            .find(|alias| {
+                let alias_path = alias.path().trim_matches('/');
+                let url_path = url.path().trim_matches('/');
+                same_origin(&url, alias)
+                    && (alias_path.is_empty()
+                        || url_path == alias_path
+                        || url_path.starts_with(&format!("{}/", alias_path)))
+            })
+            .map(|alias| rehome(&url, alias, &config.channel_alias))
+            .unwrap_or(url);
+        // Recompute `path` since Case 3 may have just rehomed `url` to a different one.
+        let path = url.path().trim_end_matches('/');
+
+        // Case 4: custom_channels matches: the url is hosted under one of the explicitly
+        // configured custom channel locations.
+        for (name, custom_url) in config.custom_channels.iter() {
+            if let Some(subpath) = strip_channel_prefix(&url, custom_url, name) {
+                return Self::from_custom_location(custom_url, name, subpath, platforms);
+            }
+        }
+
+        // Case 5: channel_alias match: the url is hosted under the (possibly just rewritten)
+        // channel alias, so the name is simply the remaining path.
+        if same_origin(&url, &config.channel_alias) {
+            let alias_path = config.channel_alias.path().trim_matches('/');
+            let name = path.trim_start_matches('/');
+            let name = name.strip_prefix(alias_path).unwrap_or(name);
+            return Self {
+                platforms: platforms.map(Into::into),
+                scheme: url.scheme().to_owned(),
+                location: host_and_port(&url),
+                name: name.trim_start_matches('/').to_owned(),
+            };
+        }
 
         if let Some(host) = url.host_str() {
             // Case 7: Fallback
@@ -121,13 +289,43 @@ impl Channel {
         }
     }
 
+    /// Builds a [`Channel`] located under `base` (a custom channel's configured Url), named
+    /// `name` with an optional `subpath` appended (e.g. a nested name or platform directory).
+    fn from_custom_location(
+        base: &Url,
+        name: &str,
+        subpath: &str,
+        platforms: Option<impl Into<SmallVec<[Platform; 2]>>>,
+    ) -> Self {
+        let name = if subpath.is_empty() {
+            name.to_owned()
+        } else {
+            format!("{}/{}", name, subpath)
+        };
+        let base_path = base.path().trim_matches('/');
+        let location = if base_path.is_empty() {
+            host_and_port(base)
+        } else {
+            format!("{}/{}", host_and_port(base), base_path)
+        };
+        Self {
+            platforms: platforms.map(Into::into),
+            scheme: base.scheme().to_owned(),
+            location,
+            name,
+        }
+    }
+
     /// Construct a channel from a name, platform and configuration.
     pub fn from_name(
         name: &str,
         platforms: Option<impl Into<SmallVec<[Platform; 2]>>>,
         config: &ChannelConfig,
     ) -> Self {
-        // TODO: custom channels
+        if let Some(custom_url) = config.custom_channels.get(name) {
+            return Self::from_custom_location(custom_url, name, "", platforms);
+        }
+
         Self {
             platforms: platforms.map(Into::into),
             scheme: config.channel_alias.scheme().to_owned(),
@@ -205,10 +403,10 @@ impl From<url::ParseError> for ParseChannelError {
     }
 }
 
+type ParsedPlatforms<'a> = Result<(Option<SmallVec<[Platform; 2]>>, &'a str), ParsePlatformError>;
+
 /// Extract the platforms from the given human readable channel.
-fn parse_platforms(
-    channel: &str,
-) -> Result<(Option<SmallVec<[Platform; 2]>>, &str), ParsePlatformError> {
+fn parse_platforms(channel: &str) -> ParsedPlatforms<'_> {
     if channel.rfind(']').is_some() {
         if let Some(start_platform_idx) = channel.find('[') {
             let platform_part = &channel[start_platform_idx + 1..channel.len() - 1];
@@ -228,7 +426,7 @@ fn parse_platforms(
 /// as platform agnostic platforms.
 pub const fn default_platforms() -> &'static [Platform] {
     const CURRENT_PLATFORMS: [Platform; 2] = [Platform::current(), Platform::NoArch];
-    return &CURRENT_PLATFORMS;
+    &CURRENT_PLATFORMS
 }
 
 /// Parses the schema part of the human-readable channel. Returns the scheme part if it exists.
@@ -256,6 +454,59 @@ fn parse_scheme(channel: &str) -> Option<&str> {
     }
 }
 
+/// Returns true if `url` and `other` share the same scheme, host and port.
+fn same_origin(url: &Url, other: &Url) -> bool {
+    url.scheme() == other.scheme() && url.host_str() == other.host_str() && url.port() == other.port()
+}
+
+/// Returns the `host[:port]` part of a Url, used as (the start of) a [`Channel::location`].
+fn host_and_port(url: &Url) -> String {
+    let host = url.host_str().unwrap_or("");
+    match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_owned(),
+    }
+}
+
+/// If `url` is hosted under `prefix` with `name` as the first path segment that follows, returns
+/// the remaining subpath (e.g. a platform directory). Returns `None` if `url` does not match.
+fn strip_channel_prefix<'a>(url: &'a Url, prefix: &Url, name: &str) -> Option<&'a str> {
+    if !same_origin(url, prefix) {
+        return None;
+    }
+
+    let prefix_path = prefix.path().trim_matches('/');
+    let url_path = url.path().trim_matches('/');
+
+    let rest = if prefix_path.is_empty() {
+        url_path
+    } else {
+        url_path.strip_prefix(prefix_path)?.trim_start_matches('/')
+    };
+
+    let rest = rest.strip_prefix(name)?;
+    if !rest.is_empty() && !rest.starts_with('/') {
+        return None;
+    }
+    Some(rest.trim_start_matches('/'))
+}
+
+/// Re-homes `url` from `old_alias` to `new_alias`, keeping the part of the path that follows
+/// `old_alias`'s own path.
+fn rehome(url: &Url, old_alias: &Url, new_alias: &Url) -> Url {
+    let old_alias_path = old_alias.path().trim_matches('/');
+    let url_path = url.path().trim_matches('/');
+    let remainder = url_path
+        .strip_prefix(old_alias_path)
+        .unwrap_or(url_path)
+        .trim_start_matches('/');
+
+    let new_path = format!("{}/{}", new_alias.path().trim_end_matches('/'), remainder);
+    let mut new_url = new_alias.clone();
+    new_url.set_path(new_path.trim_end_matches('/'));
+    new_url
+}
+
 /// Returns true if the specified string is considered to be a path
 fn is_path(path: &str) -> bool {
     let re = regex::Regex::new(r"(\./|\.\.|~|/|[a-zA-Z]:[/\\]|\\\\|//)").unwrap();
@@ -264,8 +515,11 @@ fn is_path(path: &str) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use super::{parse_scheme, Channel, ChannelConfig, Platform};
+    use super::{parse_scheme, Channel, ChannelConfig, Merge, PartialChannelConfig, Platform};
     use smallvec::smallvec;
+    use std::collections::HashMap;
+    use std::str::FromStr;
+    use url::Url;
 
     #[test]
     fn test_parse_scheme() {
@@ -311,4 +565,114 @@ mod tests {
         assert_eq!(channel.name, "pkgs/main");
         assert_eq!(channel.platforms, Some(smallvec![platform]));
     }
+
+    #[test]
+    fn custom_channel_from_name() {
+        let config = ChannelConfig {
+            custom_channels: HashMap::from([(
+                "my-channel".to_string(),
+                Url::from_str("https://server.com/private").unwrap(),
+            )]),
+            ..ChannelConfig::default()
+        };
+
+        let channel = Channel::from_str("my-channel", &config).unwrap();
+        assert_eq!(channel.scheme, "https");
+        assert_eq!(channel.location, "server.com/private");
+        assert_eq!(channel.name, "my-channel");
+
+        let channel =
+            Channel::from_str("https://server.com/private/my-channel/linux-64", &config)
+                .unwrap();
+        assert_eq!(channel.location, "server.com/private");
+        assert_eq!(channel.name, "my-channel/linux-64");
+    }
+
+    #[test]
+    fn migrated_channel_alias_rewrite() {
+        let config = ChannelConfig {
+            migrated_channel_aliases: vec![Url::from_str("https://old.anaconda.org").unwrap()],
+            ..ChannelConfig::default()
+        };
+
+        let channel =
+            Channel::from_str("https://old.anaconda.org/conda-forge", &config).unwrap();
+        assert_eq!(channel.location, "conda.anaconda.org");
+        assert_eq!(channel.name, "conda-forge");
+    }
+
+    #[test]
+    fn migrated_channel_alias_rewrite_strips_old_alias_path() {
+        let config = ChannelConfig {
+            migrated_channel_aliases: vec![
+                Url::from_str("https://old.anaconda.org/legacy").unwrap(),
+            ],
+            ..ChannelConfig::default()
+        };
+
+        let channel =
+            Channel::from_str("https://old.anaconda.org/legacy/conda-forge", &config).unwrap();
+        assert_eq!(channel.location, "conda.anaconda.org");
+        assert_eq!(channel.name, "conda-forge");
+    }
+
+    #[test]
+    fn migrated_custom_channel_rewrite() {
+        let config = ChannelConfig {
+            custom_channels: HashMap::from([(
+                "my-channel".to_string(),
+                Url::from_str("https://new.server.com/private").unwrap(),
+            )]),
+            migrated_custom_channels: HashMap::from([(
+                "my-channel".to_string(),
+                Url::from_str("https://old.server.com/private").unwrap(),
+            )]),
+            ..ChannelConfig::default()
+        };
+
+        let channel =
+            Channel::from_str("https://old.server.com/private/my-channel", &config).unwrap();
+        assert_eq!(channel.location, "new.server.com/private");
+        assert_eq!(channel.name, "my-channel");
+    }
+
+    #[test]
+    fn multichannel_expansion() {
+        let config = ChannelConfig {
+            custom_multichannels: HashMap::from([(
+                "defaults".to_string(),
+                vec![
+                    Channel::from_str("main", &ChannelConfig::default()).unwrap(),
+                    Channel::from_str("r", &ChannelConfig::default()).unwrap(),
+                ],
+            )]),
+            ..ChannelConfig::default()
+        };
+
+        let channels = Channel::from_str_multi("defaults", &config).unwrap();
+        assert_eq!(channels.len(), 2);
+        assert_eq!(channels[0].name, "main");
+        assert_eq!(channels[1].name, "r");
+
+        let channels = Channel::from_str_multi("conda-forge", &config).unwrap();
+        assert_eq!(channels.len(), 1);
+        assert_eq!(channels[0].name, "conda-forge");
+    }
+
+    #[test]
+    fn merge_partial_config() {
+        let base = ChannelConfig::default();
+        let override_config = PartialChannelConfig {
+            channel_alias: Some(Url::from_str("https://my.server.com").unwrap()),
+            custom_channels: HashMap::from([(
+                "my-channel".to_string(),
+                Url::from_str("https://server.com/private").unwrap(),
+            )]),
+            ..PartialChannelConfig::default()
+        };
+
+        let merged = base.merge(override_config);
+        assert_eq!(merged.channel_alias.as_str(), "https://my.server.com/");
+        assert!(merged.custom_channels.contains_key("my-channel"));
+    }
 }
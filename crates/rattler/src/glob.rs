@@ -0,0 +1,114 @@
+use regex::Regex;
+use std::collections::HashMap;
+
+/// Compiles a conda-style build-string glob (only `*` is a special character, matching any
+/// sequence) into an anchored [`Regex`].
+pub(crate) fn glob_to_regex(pattern: &str) -> Regex {
+    let mut regex = String::from("^");
+    for (i, part) in pattern.split('*').enumerate() {
+        if i > 0 {
+            regex.push_str(".*");
+        }
+        regex.push_str(&regex::escape(part));
+    }
+    regex.push('$');
+    Regex::new(&regex).expect("glob pattern always compiles to a valid regex")
+}
+
+/// Returns true if there exists a string matched by both `a` and `b`. Both patterns only use `*`
+/// as a wildcard, so this reduces to the classic "do two wildcard patterns intersect" problem:
+/// walk both patterns in lockstep, letting a `*` absorb zero or more characters — including
+/// characters that are "produced" by the other pattern's own literals.
+pub(crate) fn globs_overlap(a: &str, b: &str) -> bool {
+    let a = tokenize(a);
+    let b = tokenize(b);
+    let mut memo = HashMap::new();
+    overlap(&a, &b, 0, 0, &mut memo)
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+enum Token {
+    Char(char),
+    Star,
+}
+
+fn tokenize(pattern: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    for c in pattern.chars() {
+        if c == '*' {
+            if tokens.last() != Some(&Token::Star) {
+                tokens.push(Token::Star);
+            }
+        } else {
+            tokens.push(Token::Char(c));
+        }
+    }
+    tokens
+}
+
+fn overlap(
+    a: &[Token],
+    b: &[Token],
+    i: usize,
+    j: usize,
+    memo: &mut HashMap<(usize, usize), bool>,
+) -> bool {
+    if let Some(&cached) = memo.get(&(i, j)) {
+        return cached;
+    }
+
+    let result = if i == a.len() && j == b.len() {
+        true
+    } else if i == a.len() {
+        b[j..].iter().all(|t| *t == Token::Star)
+    } else if j == b.len() {
+        a[i..].iter().all(|t| *t == Token::Star)
+    } else {
+        match (a[i], b[j]) {
+            (Token::Char(x), Token::Char(y)) => x == y && overlap(a, b, i + 1, j + 1, memo),
+            (Token::Star, Token::Star) => {
+                overlap(a, b, i + 1, j + 1, memo)
+                    || overlap(a, b, i + 1, j, memo)
+                    || overlap(a, b, i, j + 1, memo)
+            }
+            (Token::Star, _) => overlap(a, b, i + 1, j, memo) || overlap(a, b, i, j + 1, memo),
+            (_, Token::Star) => overlap(a, b, i, j + 1, memo) || overlap(a, b, i + 1, j, memo),
+        }
+    };
+
+    memo.insert((i, j), result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{glob_to_regex, globs_overlap};
+
+    #[test]
+    fn glob_matches_prefix_and_suffix() {
+        assert!(glob_to_regex("py38_*").is_match("py38_0"));
+        assert!(!glob_to_regex("py38_*").is_match("py39_0"));
+        assert!(glob_to_regex("*_0").is_match("py38_0"));
+    }
+
+    #[test]
+    fn identical_patterns_overlap() {
+        assert!(globs_overlap("py38_*", "py38_*"));
+    }
+
+    #[test]
+    fn disjoint_literal_patterns_do_not_overlap() {
+        assert!(!globs_overlap("py38_0", "py39_0"));
+    }
+
+    #[test]
+    fn patterns_with_compatible_wildcards_overlap() {
+        assert!(globs_overlap("py38_*", "*_0"));
+        assert!(globs_overlap("py3*_0", "py38_*"));
+    }
+
+    #[test]
+    fn patterns_with_incompatible_literals_do_not_overlap() {
+        assert!(!globs_overlap("py38_*", "py39_*"));
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{Range, Version};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// A version constraint as it appears in a [`MatchSpec`], e.g. `==1.2.3` or `>=1.0,<2.0`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct VersionSpec(Range<Version>);
+
+impl VersionSpec {
+    /// Returns the [`Range<Version>`] this spec resolves to.
+    pub fn range(&self) -> &Range<Version> {
+        &self.0
+    }
+}
+
+impl From<VersionSpec> for Range<Version> {
+    fn from(spec: VersionSpec) -> Self {
+        spec.0
+    }
+}
+
+impl FromStr for VersionSpec {
+    type Err = ParseVersionSpecError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut range = Range::any();
+        for term in s.split(',') {
+            let term = term.trim();
+            if term.is_empty() {
+                continue;
+            }
+
+            let (op, rest) = if let Some(rest) = term.strip_prefix(">=") {
+                (">=", rest)
+            } else if let Some(rest) = term.strip_prefix("<=") {
+                ("<=", rest)
+            } else if let Some(rest) = term.strip_prefix("==") {
+                ("==", rest)
+            } else if let Some(rest) = term.strip_prefix('>') {
+                (">", rest)
+            } else if let Some(rest) = term.strip_prefix('<') {
+                ("<", rest)
+            } else {
+                ("==", term)
+            };
+
+            let version = Version::from_str(rest.trim())
+                .map_err(|_| ParseVersionSpecError::InvalidVersion(rest.trim().to_owned()))?;
+
+            let term_range = match op {
+                ">=" => Range::higher_than(version),
+                "<=" => Range::lower_than(version),
+                "==" => Range::equal(version),
+                ">" => Range::strictly_higher_than(version),
+                "<" => Range::strictly_lower_than(version),
+                _ => unreachable!(),
+            };
+
+            range = range.intersection(&term_range);
+        }
+
+        Ok(Self(range))
+    }
+}
+
+/// An error that occurs when parsing a [`VersionSpec`].
+#[derive(Debug, Error, Clone, Eq, PartialEq)]
+pub enum ParseVersionSpecError {
+    #[error("'{0}' is not a valid version")]
+    InvalidVersion(String),
+}
+
+/// A match specification describes a constraint on packages, e.g. `pytorch ==1.2.3`.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct MatchSpec {
+    /// The name of the package this spec matches, if constrained.
+    pub name: Option<String>,
+
+    /// The version constraint of this spec, if any.
+    pub version: Option<VersionSpec>,
+
+    /// The exact build number this spec matches, if constrained.
+    pub build_number: Option<usize>,
+
+    /// A conda-style glob pattern (e.g. `py38_*`) the package's build string must match, if
+    /// constrained.
+    pub build: Option<String>,
+
+    /// The subdir (platform) this spec is restricted to, if any, e.g. `linux-64`.
+    pub subdir: Option<String>,
+
+    /// Features that a matching package must track.
+    pub track_features: Vec<String>,
+}
@@ -1,9 +1,10 @@
+use crate::glob::{glob_to_regex, globs_overlap};
 use crate::{MatchSpec, PackageRecord, Range, Version};
 use itertools::Itertools;
 use once_cell::sync::OnceCell;
 use pubgrub::version_set::VersionSet;
 use std::collections::hash_map::DefaultHasher;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fmt::{Display, Formatter};
 use std::hash::{Hash, Hasher};
 use std::iter::once;
@@ -12,11 +13,265 @@ use std::sync::RwLock;
 static COMPLEMENT_CACHE: OnceCell<RwLock<HashMap<MatchSpecConstraints, MatchSpecConstraints>>> =
     OnceCell::new();
 
+/// Whether prerelease versions may satisfy a [`MatchSpecElement`].
+///
+/// This can't just be a bool paired with plain AND/OR combination: the set of prerelease versions
+/// isn't an interval, so it can't be folded into `version: Range<Version>` the way other bounds
+/// are, and `compute_complement` needs to be able to express "only a prerelease satisfies this
+/// branch" as a standalone term. Hence the third `Only` state, which only ever appears as a
+/// complement of `Exclude` and is never produced by a user-facing `MatchSpec`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+enum Prerelease {
+    /// Both prerelease and stable versions satisfy this element.
+    Allow,
+    /// Only stable (non-prerelease) versions satisfy this element.
+    Exclude,
+    /// Only prerelease versions satisfy this element.
+    Only,
+}
+
+impl Prerelease {
+    fn intersection(self, other: Self) -> Option<Self> {
+        match (self, other) {
+            (Self::Allow, x) | (x, Self::Allow) => Some(x),
+            (Self::Exclude, Self::Exclude) => Some(Self::Exclude),
+            (Self::Only, Self::Only) => Some(Self::Only),
+            (Self::Exclude, Self::Only) | (Self::Only, Self::Exclude) => None,
+        }
+    }
+
+    /// Returns the state that complements `self`, or `None` if `self` doesn't exclude anything
+    /// (i.e. there's nothing for the complement to contribute).
+    fn negate(self) -> Option<Self> {
+        match self {
+            Self::Allow => None,
+            Self::Exclude => Some(Self::Only),
+            Self::Only => Some(Self::Exclude),
+        }
+    }
+
+    fn contains(self, is_prerelease: bool) -> bool {
+        match self {
+            Self::Allow => true,
+            Self::Exclude => !is_prerelease,
+            Self::Only => is_prerelease,
+        }
+    }
+}
+
+/// A single `Must`/`MustNot` term in a [`LiteralSet`] or [`BuildMatcher`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+enum Constraint {
+    Must(String),
+    MustNot(String),
+}
+
+/// A conjunction of `Must`/`MustNot` literal-membership constraints, e.g. for `track_features`.
+/// Exposes the same `any`/`none`/`intersection`/`negations` shape as [`Range`] so it plugs into
+/// the same DNF machinery, just over a discrete domain instead of an ordered one.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+enum LiteralSet {
+    Any,
+    None,
+    Constraints(Vec<Constraint>),
+}
+
+impl LiteralSet {
+    fn any() -> Self {
+        Self::Any
+    }
+
+    fn contains(&self, values: &[String]) -> bool {
+        match self {
+            Self::Any => true,
+            Self::None => false,
+            Self::Constraints(cs) => cs.iter().all(|c| match c {
+                Constraint::Must(v) => values.iter().any(|x| x == v),
+                Constraint::MustNot(v) => values.iter().all(|x| x != v),
+            }),
+        }
+    }
+
+    fn intersection(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Self::None, _) | (_, Self::None) => Self::None,
+            (Self::Any, x) | (x, Self::Any) => x.clone(),
+            (Self::Constraints(a), Self::Constraints(b)) => {
+                let mut merged = a.clone();
+                for c in b {
+                    if !merged.contains(c) {
+                        merged.push(c.clone());
+                    }
+                }
+                for c in &merged {
+                    let contradicts = match c {
+                        Constraint::Must(v) => merged.contains(&Constraint::MustNot(v.clone())),
+                        Constraint::MustNot(v) => merged.contains(&Constraint::Must(v.clone())),
+                    };
+                    if contradicts {
+                        return Self::None;
+                    }
+                }
+                Self::Constraints(merged)
+            }
+        }
+    }
+
+    /// Returns the branches whose union is the complement of `self`.
+    fn negations(&self) -> Vec<Self> {
+        match self {
+            Self::Any => vec![],
+            Self::None => vec![Self::Any],
+            Self::Constraints(cs) => cs
+                .iter()
+                .map(|c| match c {
+                    Constraint::Must(v) => Self::Constraints(vec![Constraint::MustNot(v.clone())]),
+                    Constraint::MustNot(v) => Self::Constraints(vec![Constraint::Must(v.clone())]),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Matches a package's build string against a conda-style glob (e.g. `py38_*`), or a conjunction
+/// of several. See [`LiteralSet`] for the general shape this follows.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+enum BuildMatcher {
+    Any,
+    None,
+    Patterns(Vec<Constraint>),
+}
+
+impl BuildMatcher {
+    fn any() -> Self {
+        Self::Any
+    }
+
+    fn from_pattern(pattern: String) -> Self {
+        if pattern == "*" {
+            Self::Any
+        } else {
+            Self::Patterns(vec![Constraint::Must(pattern)])
+        }
+    }
+
+    fn contains(&self, build: &str) -> bool {
+        match self {
+            Self::Any => true,
+            Self::None => false,
+            Self::Patterns(cs) => cs.iter().all(|c| match c {
+                Constraint::Must(p) => glob_to_regex(p).is_match(build),
+                Constraint::MustNot(p) => !glob_to_regex(p).is_match(build),
+            }),
+        }
+    }
+
+    fn intersection(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Self::None, _) | (_, Self::None) => Self::None,
+            (Self::Any, x) | (x, Self::Any) => x.clone(),
+            (Self::Patterns(a), Self::Patterns(b)) => {
+                let mut merged = a.clone();
+                for c in b {
+                    if !merged.contains(c) {
+                        merged.push(c.clone());
+                    }
+                }
+                for c in &merged {
+                    let contradicts = match c {
+                        Constraint::Must(p) => merged.contains(&Constraint::MustNot(p.clone())),
+                        Constraint::MustNot(p) => merged.contains(&Constraint::Must(p.clone())),
+                    };
+                    if contradicts {
+                        return Self::None;
+                    }
+                }
+                for i in 0..merged.len() {
+                    for j in (i + 1)..merged.len() {
+                        // Two `Must` glob patterns conflict if their matched-string sets are
+                        // provably disjoint; anything else is conservatively assumed satisfiable.
+                        if let (Constraint::Must(p), Constraint::Must(q)) = (&merged[i], &merged[j])
+                        {
+                            if !globs_overlap(p, q) {
+                                return Self::None;
+                            }
+                        }
+                    }
+                }
+                Self::Patterns(merged)
+            }
+        }
+    }
+
+    fn negations(&self) -> Vec<Self> {
+        match self {
+            Self::Any => vec![],
+            Self::None => vec![Self::Any],
+            Self::Patterns(cs) => cs
+                .iter()
+                .map(|c| match c {
+                    Constraint::Must(p) => Self::Patterns(vec![Constraint::MustNot(p.clone())]),
+                    Constraint::MustNot(p) => Self::Patterns(vec![Constraint::Must(p.clone())]),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Matches a package's subdir (platform), e.g. `linux-64`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+enum SubdirMatcher {
+    Any,
+    Exact(String),
+    NotExact(BTreeSet<String>),
+}
+
+impl SubdirMatcher {
+    fn any() -> Self {
+        Self::Any
+    }
+
+    fn contains(&self, subdir: &str) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Exact(s) => s == subdir,
+            Self::NotExact(excluded) => !excluded.contains(subdir),
+        }
+    }
+
+    fn intersection(&self, other: &Self) -> Option<Self> {
+        match (self, other) {
+            (Self::Any, x) | (x, Self::Any) => Some(x.clone()),
+            (Self::Exact(a), Self::Exact(b)) => (a == b).then(|| Self::Exact(a.clone())),
+            (Self::Exact(a), Self::NotExact(excluded))
+            | (Self::NotExact(excluded), Self::Exact(a)) => {
+                (!excluded.contains(a)).then(|| Self::Exact(a.clone()))
+            }
+            (Self::NotExact(a), Self::NotExact(b)) => {
+                Some(Self::NotExact(a.union(b).cloned().collect()))
+            }
+        }
+    }
+
+    /// Returns the branches whose union is the complement of `self`.
+    fn negations(&self) -> Vec<Self> {
+        match self {
+            Self::Any => vec![],
+            Self::Exact(s) => vec![Self::NotExact([s.clone()].into())],
+            Self::NotExact(excluded) => excluded.iter().cloned().map(Self::Exact).collect(),
+        }
+    }
+}
+
 /// A single AND group in a `MatchSpecConstraints`
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct MatchSpecElement {
     version: Range<Version>,
     build_number: Range<usize>,
+    build: BuildMatcher,
+    subdir: SubdirMatcher,
+    track_features: LiteralSet,
+    allow_prerelease: Prerelease,
 }
 
 impl MatchSpecElement {
@@ -25,6 +280,10 @@ impl MatchSpecElement {
         Self {
             version: Range::none(),
             build_number: Range::none(),
+            build: BuildMatcher::None,
+            subdir: SubdirMatcher::Any,
+            track_features: LiteralSet::None,
+            allow_prerelease: Prerelease::Allow,
         }
     }
 
@@ -33,6 +292,10 @@ impl MatchSpecElement {
         Self {
             version: Range::any(),
             build_number: Range::any(),
+            build: BuildMatcher::any(),
+            subdir: SubdirMatcher::any(),
+            track_features: LiteralSet::any(),
+            allow_prerelease: Prerelease::Allow,
         }
     }
 
@@ -40,19 +303,51 @@ impl MatchSpecElement {
     fn intersection(&self, other: &Self) -> Self {
         let version = self.version.intersection(&other.version);
         let build_number = self.build_number.intersection(&other.build_number);
-        if version == Range::none() || build_number == Range::none() {
-            Self::none()
-        } else {
-            Self {
-                version,
-                build_number,
+        let build = self.build.intersection(&other.build);
+        let track_features = self.track_features.intersection(&other.track_features);
+        let allow_prerelease = self.allow_prerelease.intersection(other.allow_prerelease);
+        match (self.subdir.intersection(&other.subdir), allow_prerelease) {
+            (Some(subdir), Some(allow_prerelease))
+                if version != Range::none()
+                    && build_number != Range::none()
+                    && build != BuildMatcher::None
+                    && track_features != LiteralSet::None =>
+            {
+                Self {
+                    version,
+                    build_number,
+                    build,
+                    subdir,
+                    track_features,
+                    allow_prerelease,
+                }
             }
+            _ => Self::none(),
         }
     }
 
     /// Returns true if the specified packages matches this instance
     pub fn contains(&self, package: &PackageRecord) -> bool {
-        self.version.contains(&package.version) && self.build_number.contains(&package.build_number)
+        let version_matches = match self.version.as_singleton() {
+            // An exact (`==`) constraint follows the PyTorch local-version rule: a constraint
+            // without a local segment matches any local build of the same public version, while a
+            // constraint with a local segment matches only that exact build.
+            Some(constraint) => constraint.matches_exact(&package.version),
+            None => self.version.contains(&package.version),
+        };
+        version_matches
+            && self.build_number.contains(&package.build_number)
+            && self.build.contains(&package.build)
+            && self.subdir.contains(&package.subdir)
+            && self.track_features.contains(
+                package
+                    .track_features
+                    .as_deref()
+                    .unwrap_or_default(),
+            )
+            && self
+                .allow_prerelease
+                .contains(package.version.is_prerelease())
     }
 }
 
@@ -64,14 +359,40 @@ pub struct MatchSpecConstraints {
 
 impl From<MatchSpec> for MatchSpecConstraints {
     fn from(spec: MatchSpec) -> Self {
+        let version: Range<Version> = spec.version.map(Into::into).unwrap_or_else(Range::any);
+
+        // Prereleases are excluded unless the spec's own version bound mentions one (e.g.
+        // `>=1.0rc1`), in which case the user clearly intended to opt into that prerelease line.
+        let allow_prerelease = if version.bounds().any(Version::is_prerelease) {
+            Prerelease::Allow
+        } else {
+            Prerelease::Exclude
+        };
+
+        let track_features = spec
+            .track_features
+            .into_iter()
+            .map(Constraint::Must)
+            .collect_vec();
+
         Self {
             groups: vec![MatchSpecElement {
-                version: spec.version.map(Into::into).unwrap_or_else(|| Range::any()),
+                version,
                 build_number: spec
                     .build_number
-                    .clone()
                     .map(Range::equal)
-                    .unwrap_or_else(|| Range::any()),
+                    .unwrap_or_else(Range::any),
+                build: spec
+                    .build
+                    .map(BuildMatcher::from_pattern)
+                    .unwrap_or(BuildMatcher::Any),
+                subdir: spec.subdir.map(SubdirMatcher::Exact).unwrap_or(SubdirMatcher::Any),
+                track_features: if track_features.is_empty() {
+                    LiteralSet::Any
+                } else {
+                    LiteralSet::Constraints(track_features)
+                },
+                allow_prerelease,
             }],
         }
     }
@@ -83,9 +404,70 @@ impl From<MatchSpecElement> for MatchSpecConstraints {
     }
 }
 
+impl Display for MatchSpecElement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.version)?;
+        if self.build_number != Range::any() {
+            write!(f, ",build_number{}", self.build_number)?;
+        }
+        match &self.build {
+            BuildMatcher::Any => {}
+            BuildMatcher::None => write!(f, ",build={{}}")?,
+            BuildMatcher::Patterns(cs) => {
+                for c in cs {
+                    match c {
+                        Constraint::Must(p) => write!(f, ",build={p}")?,
+                        Constraint::MustNot(p) => write!(f, ",build!={p}")?,
+                    }
+                }
+            }
+        }
+        match &self.subdir {
+            SubdirMatcher::Any => {}
+            SubdirMatcher::Exact(s) => write!(f, ",subdir={s}")?,
+            SubdirMatcher::NotExact(excluded) => {
+                for s in excluded {
+                    write!(f, ",subdir!={s}")?;
+                }
+            }
+        }
+        match &self.track_features {
+            LiteralSet::Any => {}
+            LiteralSet::None => write!(f, ",track_features={{}}")?,
+            LiteralSet::Constraints(cs) => {
+                for c in cs {
+                    match c {
+                        Constraint::Must(v) => write!(f, ",track_features={v}")?,
+                        Constraint::MustNot(v) => write!(f, ",track_features!={v}")?,
+                    }
+                }
+            }
+        }
+        match self.allow_prerelease {
+            // `Allow` places no additional restriction beyond the other dimensions, so (like
+            // `Range::any()` or `BuildMatcher::Any`) it renders as nothing.
+            Prerelease::Allow => {}
+            Prerelease::Exclude => write!(f, ",allow_prerelease=false")?,
+            Prerelease::Only => write!(f, ",allow_prerelease=only")?,
+        }
+        Ok(())
+    }
+}
+
+/// Renders the DNF back into conda `MatchSpec` notation, e.g. `>=1.0,<2.0|==2.0.rc.1`, for use in
+/// pubgrub's conflict/explanation output.
 impl Display for MatchSpecConstraints {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "bla")
+        if self.groups.is_empty() {
+            return write!(f, "{{}}");
+        }
+        for (i, group) in self.groups.iter().enumerate() {
+            if i > 0 {
+                write!(f, "|")?;
+            }
+            write!(f, "{group}")?;
+        }
+        Ok(())
     }
 }
 
@@ -104,7 +486,7 @@ impl MatchSpecConstraints {
                 if version_complement != Range::none() {
                     let version_complement = MatchSpecElement {
                         version: version_complement,
-                        build_number: Range::any(),
+                        ..MatchSpecElement::any()
                     };
                     next.extend(
                         groups
@@ -117,8 +499,8 @@ impl MatchSpecConstraints {
                 let build_complement = spec.build_number.negate();
                 if build_complement != Range::none() {
                     let build_complement = MatchSpecElement {
-                        version: Range::any(),
                         build_number: build_complement,
+                        ..MatchSpecElement::any()
                     };
                     next.extend(
                         groups
@@ -128,6 +510,58 @@ impl MatchSpecConstraints {
                     );
                 }
 
+                for build in spec.build.negations() {
+                    let build_complement = MatchSpecElement {
+                        build,
+                        ..MatchSpecElement::any()
+                    };
+                    next.extend(
+                        groups
+                            .iter()
+                            .map(|o| o.intersection(&build_complement))
+                            .filter(|n| n != &mse_none),
+                    );
+                }
+
+                for subdir in spec.subdir.negations() {
+                    let subdir_complement = MatchSpecElement {
+                        subdir,
+                        ..MatchSpecElement::any()
+                    };
+                    next.extend(
+                        groups
+                            .iter()
+                            .map(|o| o.intersection(&subdir_complement))
+                            .filter(|n| n != &mse_none),
+                    );
+                }
+
+                for track_features in spec.track_features.negations() {
+                    let track_features_complement = MatchSpecElement {
+                        track_features,
+                        ..MatchSpecElement::any()
+                    };
+                    next.extend(
+                        groups
+                            .iter()
+                            .map(|o| o.intersection(&track_features_complement))
+                            .filter(|n| n != &mse_none),
+                    );
+                }
+
+                if let Some(prerelease_complement) = spec.allow_prerelease.negate() {
+                    let prerelease_complement = MatchSpecElement {
+                        allow_prerelease: prerelease_complement,
+                        ..MatchSpecElement::any()
+                    };
+                    next.extend(
+                        groups
+                            .iter()
+                            .map(|o| o.intersection(&prerelease_complement))
+                            .filter(|n| n != &mse_none),
+                    );
+                }
+
                 groups = next;
             }
 
@@ -154,10 +588,7 @@ impl VersionSet for MatchSpecConstraints {
 
     fn full() -> Self {
         Self {
-            groups: vec![MatchSpecElement {
-                version: Range::any(),
-                build_number: Range::any(),
-            }],
+            groups: vec![MatchSpecElement::any()],
         }
     }
 
@@ -166,6 +597,10 @@ impl VersionSet for MatchSpecConstraints {
             groups: vec![MatchSpecElement {
                 version: Range::equal(v.version),
                 build_number: Range::equal(v.build_number),
+                subdir: SubdirMatcher::Exact(v.subdir),
+                build: BuildMatcher::from_pattern(v.build),
+                allow_prerelease: Prerelease::Allow,
+                ..MatchSpecElement::any()
             }],
         }
     }
@@ -189,7 +624,7 @@ impl VersionSet for MatchSpecConstraints {
             write_lock.insert(self.clone(), complement.clone());
         }
 
-        return complement;
+        complement
     }
 
     fn intersection(&self, other: &Self) -> Self {
@@ -230,16 +665,17 @@ impl VersionSet for MatchSpecConstraints {
 
 #[cfg(test)]
 mod tests {
-    use crate::match_spec_constraints::MatchSpecConstraints;
-    use crate::{PackageRecord, Version};
+    use crate::match_spec_constraints::{MatchSpecConstraints, MatchSpecElement};
+    use crate::{MatchSpec, PackageRecord, Range, Version, VersionSpec};
     use pubgrub::version_set::VersionSet;
     use std::str::FromStr;
 
-    #[test]
-    fn complement() {
-        let record = PackageRecord {
-            name: "".to_string(),
-            version: Version::from_str("1.2.3").unwrap(),
+    /// Builds a minimal [`PackageRecord`] with the given name and version, for use in tests that
+    /// only care about name/version matching.
+    fn test_record(name: &str, version: &str) -> PackageRecord {
+        PackageRecord {
+            name: name.to_string(),
+            version: Version::from_str(version).unwrap(),
             build: "".to_string(),
             build_number: 1,
             subdir: "".to_string(),
@@ -257,7 +693,12 @@ mod tests {
             timestamp: None,
             date: None,
             size: None,
-        };
+        }
+    }
+
+    #[test]
+    fn complement() {
+        let record = test_record("", "1.2.3");
 
         let constraint = MatchSpecConstraints::singleton(record.clone());
 
@@ -292,4 +733,212 @@ mod tests {
             constraint.complement().union(&constraint)
         );
     }
+
+    #[test]
+    fn pytorch_local_version_matching() {
+        let spec = MatchSpec {
+            name: Some("pytorch".to_string()),
+            version: Some(VersionSpec::from_str("==1.2.3").unwrap()),
+            ..Default::default()
+        };
+        let element: MatchSpecElement = MatchSpecConstraints::from(spec).groups[0].clone();
+
+        assert!(element.contains(&test_record("pytorch", "1.2.3+cu118")));
+        assert!(element.contains(&test_record("pytorch", "1.2.3")));
+        assert!(!element.contains(&test_record("pytorch", "1.2.4+cu118")));
+
+        let pinned_build_spec = MatchSpec {
+            name: Some("pytorch".to_string()),
+            version: Some(VersionSpec::from_str("==1.2.3+cu118").unwrap()),
+            build_number: None,
+            ..Default::default()
+        };
+        let pinned_build: MatchSpecElement =
+            MatchSpecConstraints::from(pinned_build_spec).groups[0].clone();
+
+        assert!(pinned_build.contains(&test_record("pytorch", "1.2.3+cu118")));
+        assert!(!pinned_build.contains(&test_record("pytorch", "1.2.3+cpu")));
+        assert!(!pinned_build.contains(&test_record("pytorch", "1.2.3")));
+    }
+
+    #[test]
+    fn bare_constraint_skips_prerelease() {
+        let spec = MatchSpec {
+            name: Some("numpy".to_string()),
+            version: Some(VersionSpec::from_str(">=1.0").unwrap()),
+            build_number: None,
+            ..Default::default()
+        };
+        let element: MatchSpecElement = MatchSpecConstraints::from(spec).groups[0].clone();
+
+        assert!(element.contains(&test_record("numpy", "1.5")));
+        assert!(!element.contains(&test_record("numpy", "2.0b1")));
+    }
+
+    #[test]
+    fn prerelease_bound_opts_in_to_that_line() {
+        let spec = MatchSpec {
+            name: Some("numpy".to_string()),
+            version: Some(VersionSpec::from_str(">=2.0b1").unwrap()),
+            build_number: None,
+            ..Default::default()
+        };
+        let element: MatchSpecElement = MatchSpecConstraints::from(spec).groups[0].clone();
+
+        assert!(element.contains(&test_record("numpy", "2.0b1")));
+        assert!(element.contains(&test_record("numpy", "2.0")));
+    }
+
+    #[test]
+    fn prerelease_complement_roundtrips() {
+        let spec = MatchSpec {
+            name: Some("numpy".to_string()),
+            version: Some(VersionSpec::from_str(">=1.0").unwrap()),
+            build_number: None,
+            ..Default::default()
+        };
+        let constraint = MatchSpecConstraints::from(spec);
+
+        let stable = test_record("numpy", "1.5");
+        let prerelease = test_record("numpy", "2.0b1");
+
+        assert!(constraint.contains(&stable));
+        assert!(!constraint.complement().contains(&stable));
+
+        // The complement picks up the prerelease that the original spec excluded, since it still
+        // falls within the `>=1.0` version range.
+        assert!(constraint.complement().contains(&prerelease));
+
+        assert_eq!(constraint.complement().complement(), constraint);
+    }
+
+    /// Builds a [`PackageRecord`] with an explicit build string, for tests of build-glob matching.
+    fn record_with_build(name: &str, version: &str, build: &str) -> PackageRecord {
+        PackageRecord {
+            build: build.to_string(),
+            ..test_record(name, version)
+        }
+    }
+
+    #[test]
+    fn overlapping_build_globs_intersect_non_trivially() {
+        let py38 = MatchSpec {
+            name: Some("numpy".to_string()),
+            version: Some(VersionSpec::from_str("==1.2.3").unwrap()),
+            build: Some("py38_*".to_string()),
+            ..Default::default()
+        };
+        let zero_build = MatchSpec {
+            name: Some("numpy".to_string()),
+            version: Some(VersionSpec::from_str("==1.2.3").unwrap()),
+            build: Some("*_0".to_string()),
+            ..Default::default()
+        };
+
+        let intersection = MatchSpecConstraints::from(py38)
+            .intersection(&MatchSpecConstraints::from(zero_build));
+
+        assert!(intersection.contains(&record_with_build("numpy", "1.2.3", "py38_0")));
+        assert!(!intersection.contains(&record_with_build("numpy", "1.2.3", "py39_0")));
+    }
+
+    #[test]
+    fn disjoint_build_globs_never_intersect() {
+        let py38 = MatchSpec {
+            name: Some("numpy".to_string()),
+            version: Some(VersionSpec::from_str("==1.2.3").unwrap()),
+            build: Some("py38_*".to_string()),
+            ..Default::default()
+        };
+        let py39 = MatchSpec {
+            name: Some("numpy".to_string()),
+            version: Some(VersionSpec::from_str("==1.2.3").unwrap()),
+            build: Some("py39_*".to_string()),
+            ..Default::default()
+        };
+
+        let intersection =
+            MatchSpecConstraints::from(py38).intersection(&MatchSpecConstraints::from(py39));
+
+        assert_eq!(intersection, MatchSpecConstraints::empty());
+    }
+
+    #[test]
+    fn display_renders_singleton_as_equality() {
+        let spec = MatchSpec {
+            name: Some("numpy".to_string()),
+            version: Some(VersionSpec::from_str("==1.2.3").unwrap()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            MatchSpecConstraints::from(spec).to_string(),
+            "==1.2.3,allow_prerelease=false"
+        );
+    }
+
+    #[test]
+    fn display_renders_open_range_and_build_number() {
+        let spec = MatchSpec {
+            name: Some("numpy".to_string()),
+            version: Some(VersionSpec::from_str(">=1.0").unwrap()),
+            build_number: Some(2),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            MatchSpecConstraints::from(spec).to_string(),
+            ">=1.0,build_number==2,allow_prerelease=false"
+        );
+    }
+
+    #[test]
+    fn display_collapses_unconstrained_version_to_star() {
+        assert_eq!(MatchSpecConstraints::full().to_string(), "*");
+    }
+
+    #[test]
+    fn display_joins_multi_group_unions_with_pipe() {
+        let rendered = MatchSpecConstraints {
+            groups: vec![
+                MatchSpecElement {
+                    version: Range::equal(Version::from_str("1.0").unwrap()),
+                    ..MatchSpecElement::any()
+                },
+                MatchSpecElement {
+                    version: Range::equal(Version::from_str("2.0").unwrap()),
+                    ..MatchSpecElement::any()
+                },
+            ],
+        }
+        .to_string();
+
+        assert_eq!(rendered, "==1.0|==2.0");
+    }
+
+    #[test]
+    fn display_distinguishes_allow_prerelease_from_the_default_exclude() {
+        let excludes_prerelease = MatchSpec {
+            name: Some("numpy".to_string()),
+            version: Some(VersionSpec::from_str(">=1.0").unwrap()),
+            ..Default::default()
+        };
+        let allows_prerelease = MatchSpec {
+            name: Some("numpy".to_string()),
+            version: Some(VersionSpec::from_str(">=1.0rc1").unwrap()),
+            ..Default::default()
+        };
+
+        let excluded = MatchSpecConstraints::from(excludes_prerelease).to_string();
+        let allowed = MatchSpecConstraints::from(allows_prerelease).to_string();
+
+        assert_eq!(excluded, ">=1.0,allow_prerelease=false");
+        assert_eq!(allowed, ">=1.0.rc.1");
+        assert_ne!(excluded, allowed);
+    }
+
+    #[test]
+    fn display_renders_empty_constraints_as_empty_set() {
+        assert_eq!(MatchSpecConstraints::empty().to_string(), "{}");
+    }
 }
@@ -0,0 +1,45 @@
+use crate::Version;
+use serde::{Deserialize, Serialize};
+
+/// A single record in `repodata.json`, describing one built package.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PackageRecord {
+    /// The name of the package.
+    pub name: String,
+
+    /// The version of the package.
+    pub version: Version,
+
+    /// The build string of the package, e.g. `py38_0`.
+    pub build: String,
+
+    /// The build number of the package. Higher numbers are generally preferred by the solver when
+    /// everything else is equal.
+    pub build_number: usize,
+
+    /// The subdirectory (platform) this package was built for, e.g. `linux-64`.
+    pub subdir: String,
+
+    pub md5: Option<String>,
+    pub sha256: Option<String>,
+    pub arch: Option<String>,
+    pub platform: Option<String>,
+
+    /// The dependencies of this package, as raw match spec strings.
+    #[serde(default)]
+    pub depends: Vec<String>,
+
+    /// Additional constraints that are only enforced if the constrained package is already part
+    /// of the solve.
+    #[serde(default)]
+    pub constrains: Vec<String>,
+
+    pub track_features: Option<Vec<String>>,
+    pub features: Option<String>,
+    pub preferred_env: Option<String>,
+    pub license: Option<String>,
+    pub license_family: Option<String>,
+    pub timestamp: Option<u64>,
+    pub date: Option<String>,
+    pub size: Option<u64>,
+}
@@ -0,0 +1,301 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::Bound::{self, Excluded, Included, Unbounded};
+
+/// A set of `T` represented as a union of disjoint, non-adjacent intervals, kept sorted by lower
+/// bound. This is the value-set abstraction the DNF machinery in
+/// [`crate::match_spec_constraints`] is built on: on top of membership (`contains`) it exposes the
+/// small boolean algebra (`intersection`, `negate`, `union`) pubgrub's `VersionSet` needs.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Range<T> {
+    segments: Vec<(Bound<T>, Bound<T>)>,
+}
+
+impl<T: Ord + Clone> Range<T> {
+    /// A range that matches nothing.
+    pub fn none() -> Self {
+        Self { segments: vec![] }
+    }
+
+    /// A range that matches everything.
+    pub fn any() -> Self {
+        Self {
+            segments: vec![(Unbounded, Unbounded)],
+        }
+    }
+
+    /// A range that matches only `v`.
+    pub fn equal(v: T) -> Self {
+        Self {
+            segments: vec![(Included(v.clone()), Included(v))],
+        }
+    }
+
+    /// A range that matches everything greater than or equal to `v`.
+    pub fn higher_than(v: T) -> Self {
+        Self {
+            segments: vec![(Included(v), Unbounded)],
+        }
+    }
+
+    /// A range that matches everything strictly greater than `v`.
+    pub fn strictly_higher_than(v: T) -> Self {
+        Self {
+            segments: vec![(Excluded(v), Unbounded)],
+        }
+    }
+
+    /// A range that matches everything strictly lower than `v`.
+    pub fn strictly_lower_than(v: T) -> Self {
+        Self {
+            segments: vec![(Unbounded, Excluded(v))],
+        }
+    }
+
+    /// A range that matches everything lower than or equal to `v`.
+    pub fn lower_than(v: T) -> Self {
+        Self {
+            segments: vec![(Unbounded, Included(v))],
+        }
+    }
+
+    /// A range that matches `[low, high)`.
+    pub fn between(low: T, high: T) -> Self {
+        Self {
+            segments: vec![(Included(low), Excluded(high))],
+        }
+    }
+
+    /// If this range matches exactly one value (as constructed by [`Range::equal`]), returns a
+    /// reference to it.
+    pub fn as_singleton(&self) -> Option<&T> {
+        match self.segments.as_slice() {
+            [(Included(lo), Included(hi))] if lo == hi => Some(lo),
+            _ => None,
+        }
+    }
+
+    /// Iterates over every finite bound value used by this range, in no particular order. Useful
+    /// for inspecting what values a range was built from (e.g. to check whether any of them has
+    /// some property), since the range itself only knows about ordering, not about `T`.
+    pub fn bounds(&self) -> impl Iterator<Item = &T> + '_ {
+        self.segments.iter().flat_map(|(lo, hi)| {
+            let lo = match lo {
+                Included(v) | Excluded(v) => Some(v),
+                Unbounded => None,
+            };
+            let hi = match hi {
+                Included(v) | Excluded(v) => Some(v),
+                Unbounded => None,
+            };
+            lo.into_iter().chain(hi)
+        })
+    }
+
+    /// Returns true if `v` falls within this range.
+    pub fn contains(&self, v: &T) -> bool {
+        self.segments.iter().any(|(lo, hi)| {
+            let above_lo = match lo {
+                Unbounded => true,
+                Included(lo) => v >= lo,
+                Excluded(lo) => v > lo,
+            };
+            let below_hi = match hi {
+                Unbounded => true,
+                Included(hi) => v <= hi,
+                Excluded(hi) => v < hi,
+            };
+            above_lo && below_hi
+        })
+    }
+
+    /// Returns the complement of this range: the gaps before, between and after its segments.
+    pub fn negate(&self) -> Self {
+        if self.segments.is_empty() {
+            return Self::any();
+        }
+
+        let mut segments = Vec::new();
+        let mut gap_lo: Bound<T> = Unbounded;
+
+        for (lo, hi) in &self.segments {
+            if !matches!(lo, Unbounded) {
+                let gap_hi = flip(lo.clone());
+                if lower_less_than_upper(&gap_lo, &gap_hi) {
+                    segments.push((gap_lo, gap_hi));
+                }
+            }
+            gap_lo = flip(hi.clone());
+        }
+
+        if !matches!(gap_lo, Unbounded) {
+            segments.push((gap_lo, Unbounded));
+        }
+
+        Self { segments }
+    }
+
+    /// Returns the intersection of `self` and `other`.
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut segments = Vec::new();
+
+        for (a_lo, a_hi) in &self.segments {
+            for (b_lo, b_hi) in &other.segments {
+                let lo = max_lower(a_lo, b_lo);
+                let hi = min_upper(a_hi, b_hi);
+                if lower_less_than_upper(&lo, &hi) {
+                    segments.push((lo, hi));
+                }
+            }
+        }
+
+        segments.sort_by(|a, b| cmp_lower(&a.0, &b.0));
+        Self { segments }
+    }
+
+    /// Returns the union of `self` and `other`.
+    pub fn union(&self, other: &Self) -> Self {
+        self.negate().intersection(&other.negate()).negate()
+    }
+}
+
+/// Flips `Included`/`Excluded` in place, leaving `Unbounded` as-is. A segment boundary touching
+/// point `x` becomes, from the complement's point of view, a boundary that touches the same point
+/// with the opposite inclusivity.
+fn flip<T>(b: Bound<T>) -> Bound<T> {
+    match b {
+        Unbounded => Unbounded,
+        Included(v) => Excluded(v),
+        Excluded(v) => Included(v),
+    }
+}
+
+fn max_lower<T: Ord + Clone>(a: &Bound<T>, b: &Bound<T>) -> Bound<T> {
+    match (a, b) {
+        (Unbounded, other) | (other, Unbounded) => other.clone(),
+        (Included(x), Included(y)) => Included(if x >= y { x.clone() } else { y.clone() }),
+        (Excluded(x), Excluded(y)) => Excluded(if x >= y { x.clone() } else { y.clone() }),
+        (Included(x), Excluded(y)) | (Excluded(y), Included(x)) => {
+            if x > y {
+                Included(x.clone())
+            } else {
+                Excluded(y.clone())
+            }
+        }
+    }
+}
+
+fn min_upper<T: Ord + Clone>(a: &Bound<T>, b: &Bound<T>) -> Bound<T> {
+    match (a, b) {
+        (Unbounded, other) | (other, Unbounded) => other.clone(),
+        (Included(x), Included(y)) => Included(if x <= y { x.clone() } else { y.clone() }),
+        (Excluded(x), Excluded(y)) => Excluded(if x <= y { x.clone() } else { y.clone() }),
+        (Included(x), Excluded(y)) | (Excluded(y), Included(x)) => {
+            if x < y {
+                Included(x.clone())
+            } else {
+                Excluded(y.clone())
+            }
+        }
+    }
+}
+
+fn lower_less_than_upper<T: Ord>(lo: &Bound<T>, hi: &Bound<T>) -> bool {
+    match (lo, hi) {
+        (Unbounded, _) | (_, Unbounded) => true,
+        (Included(lo), Included(hi)) => lo <= hi,
+        (Included(lo), Excluded(hi)) | (Excluded(lo), Included(hi)) | (Excluded(lo), Excluded(hi)) => {
+            lo < hi
+        }
+    }
+}
+
+fn cmp_lower<T: Ord>(a: &Bound<T>, b: &Bound<T>) -> Ordering {
+    match (a, b) {
+        (Unbounded, Unbounded) => Ordering::Equal,
+        (Unbounded, _) => Ordering::Less,
+        (_, Unbounded) => Ordering::Greater,
+        (Included(x), Included(y)) | (Excluded(x), Excluded(y)) => x.cmp(y),
+        (Included(x), Excluded(y)) => x.cmp(y).then(Ordering::Less),
+        (Excluded(x), Included(y)) => x.cmp(y).then(Ordering::Greater),
+    }
+}
+
+impl<T: fmt::Display + Ord + Clone> fmt::Display for Range<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.segments.is_empty() {
+            return write!(f, "{{}}");
+        }
+        if self == &Self::any() {
+            return write!(f, "*");
+        }
+
+        for (i, (lo, hi)) in self.segments.iter().enumerate() {
+            if i > 0 {
+                write!(f, "|")?;
+            }
+            match (lo, hi) {
+                (Included(lo), Included(hi)) if lo == hi => write!(f, "=={lo}")?,
+                (Unbounded, Unbounded) => write!(f, "*")?,
+                (Included(lo), Unbounded) => write!(f, ">={lo}")?,
+                (Excluded(lo), Unbounded) => write!(f, ">{lo}")?,
+                (Unbounded, Included(hi)) => write!(f, "<={hi}")?,
+                (Unbounded, Excluded(hi)) => write!(f, "<{hi}")?,
+                (Included(lo), Included(hi)) => write!(f, ">={lo},<={hi}")?,
+                (Included(lo), Excluded(hi)) => write!(f, ">={lo},<{hi}")?,
+                (Excluded(lo), Included(hi)) => write!(f, ">{lo},<={hi}")?,
+                (Excluded(lo), Excluded(hi)) => write!(f, ">{lo},<{hi}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Range;
+
+    #[test]
+    fn equal_contains_only_itself() {
+        let r = Range::equal(5);
+        assert!(r.contains(&5));
+        assert!(!r.contains(&4));
+        assert!(!r.contains(&6));
+    }
+
+    #[test]
+    fn negate_of_equal_excludes_only_that_value() {
+        let r = Range::equal(5);
+        let negated = r.negate();
+        assert!(!negated.contains(&5));
+        assert!(negated.contains(&4));
+        assert!(negated.contains(&6));
+    }
+
+    #[test]
+    fn double_negate_is_identity() {
+        let r = Range::higher_than(3);
+        assert_eq!(r.negate().negate(), r);
+    }
+
+    #[test]
+    fn intersection_of_disjoint_ranges_is_none() {
+        let a = Range::lower_than(3);
+        let b = Range::higher_than(5);
+        assert_eq!(a.intersection(&b), Range::none());
+    }
+
+    #[test]
+    fn union_of_complementary_ranges_is_any() {
+        let r = Range::equal(5);
+        assert_eq!(r.union(&r.negate()), Range::any());
+    }
+
+    #[test]
+    fn display_renders_conda_style_bounds() {
+        assert_eq!(Range::<i32>::any().to_string(), "*");
+        assert_eq!(Range::equal(5).to_string(), "==5");
+        assert_eq!(Range::higher_than(5).to_string(), ">=5");
+        assert_eq!(Range::between(1, 2).to_string(), ">=1,<2");
+    }
+}
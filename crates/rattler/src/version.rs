@@ -0,0 +1,365 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// A single `.`-delimited piece of a version string. Runs of digits compare numerically; any
+/// other run of characters is lower-cased, and a prerelease marker (see [`prerelease_rank`]) sorts
+/// below every other identifier and by its rank, while the rest compare as strings. A numeral
+/// always sorts greater than an identifier, which is what makes a local-version segment like
+/// `cu118` sort below `2`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+enum VersionComponent {
+    Numeral(u64),
+    Ident(String),
+}
+
+impl Ord for VersionComponent {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Numeral(a), Self::Numeral(b)) => a.cmp(b),
+            (Self::Ident(a), Self::Ident(b)) => match (prerelease_rank(a), prerelease_rank(b)) {
+                (Some(ra), Some(rb)) => ra.cmp(&rb).then_with(|| a.cmp(b)),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => a.cmp(b),
+            },
+            (Self::Numeral(_), Self::Ident(_)) => Ordering::Greater,
+            (Self::Ident(_), Self::Numeral(_)) => Ordering::Less,
+        }
+    }
+}
+
+/// The PEP 440/conda precedence rank of a prerelease identifier, in increasing order of maturity:
+/// `dev` sorts below `a`/`alpha`, which sorts below `b`/`beta`, which sorts below `rc`. Returns
+/// `None` for any identifier that isn't one of these markers (e.g. a `post`-release suffix or an
+/// arbitrary local-segment tag), which is ranked above every prerelease marker.
+fn prerelease_rank(ident: &str) -> Option<u8> {
+    match ident {
+        "dev" => Some(0),
+        "a" | "alpha" => Some(1),
+        "b" | "beta" => Some(2),
+        "rc" => Some(3),
+        _ => None,
+    }
+}
+
+impl PartialOrd for VersionComponent {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for VersionComponent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Numeral(n) => write!(f, "{n}"),
+            Self::Ident(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+/// A conda version, e.g. `1.2.3`, `1.0rc1`, or `1.2.3+cu118`.
+///
+/// A version is made up of a "public" part and an optional "local" part, separated by `+`, as
+/// used by PyTorch-style builds. Two versions whose public parts are equal but whose local parts
+/// differ are NOT equal, but the local part only ever makes a version compare *greater*: a
+/// version with a local segment always sorts immediately after the otherwise-equal version
+/// without one. See [`Version::matches_exact`] for the special matching rule this enables.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct Version {
+    public: Vec<VersionComponent>,
+    local: Vec<VersionComponent>,
+}
+
+impl From<Version> for String {
+    fn from(version: Version) -> Self {
+        version.to_string()
+    }
+}
+
+impl TryFrom<String> for Version {
+    type Error = ParseVersionError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::from_str(&value)
+    }
+}
+
+impl Version {
+    /// Returns true if this version has a local segment (the part after `+`).
+    pub fn has_local(&self) -> bool {
+        !self.local.is_empty()
+    }
+
+    /// Returns true if the public part of this version looks like a prerelease, i.e. it ends in a
+    /// `dev`, `a`/`alpha`, `b`/`beta` or `rc` component, optionally followed only by numeral
+    /// components (e.g. the `1` in `1.0rc1`, or the `2` in `1.dev.2`). A marker that isn't the
+    /// last identifier in the version (e.g. the `dev` in `1.dev.final`) is not a prerelease.
+    pub fn is_prerelease(&self) -> bool {
+        let Some(last_ident_idx) = self
+            .public
+            .iter()
+            .rposition(|component| matches!(component, VersionComponent::Ident(_)))
+        else {
+            return false;
+        };
+        let only_its_own_ordinal_follows = self.public[last_ident_idx + 1..]
+            .iter()
+            .all(|component| matches!(component, VersionComponent::Numeral(_)));
+
+        only_its_own_ordinal_follows
+            && matches!(
+                &self.public[last_ident_idx],
+                VersionComponent::Ident(ident) if prerelease_rank(ident).is_some()
+            )
+    }
+
+    /// Implements the PyTorch/conda matching rule for an exact (`==`) constraint: a constraint
+    /// with no local part matches any local build of the same public version, while a constraint
+    /// with a local part matches only that exact local build.
+    pub fn matches_exact(&self, candidate: &Version) -> bool {
+        if self.public != candidate.public {
+            return false;
+        }
+        self.local.is_empty() || self.local == candidate.local
+    }
+}
+
+fn tokenize(segment: &str) -> Vec<VersionComponent> {
+    let mut components = Vec::new();
+    let mut current = String::new();
+    let mut current_is_digit = false;
+
+    for c in segment.chars() {
+        if c == '.' || c == '-' || c == '_' {
+            if !current.is_empty() {
+                components.push(finish_component(&current, current_is_digit));
+                current.clear();
+            }
+            continue;
+        }
+
+        let is_digit = c.is_ascii_digit();
+        if !current.is_empty() && is_digit != current_is_digit {
+            components.push(finish_component(&current, current_is_digit));
+            current.clear();
+        }
+        current_is_digit = is_digit;
+        current.push(c);
+    }
+
+    if !current.is_empty() {
+        components.push(finish_component(&current, current_is_digit));
+    }
+
+    components
+}
+
+fn finish_component(raw: &str, is_digit: bool) -> VersionComponent {
+    if is_digit {
+        // Numeric runs are arbitrarily long in conda versions, but in practice always fit u64.
+        VersionComponent::Numeral(raw.parse().unwrap_or(u64::MAX))
+    } else {
+        VersionComponent::Ident(raw.to_lowercase())
+    }
+}
+
+impl FromStr for Version {
+    type Err = ParseVersionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(ParseVersionError::Empty);
+        }
+
+        let (public, local) = match s.split_once('+') {
+            Some((public, local)) => (public, local),
+            None => (s, ""),
+        };
+
+        if public.is_empty() {
+            return Err(ParseVersionError::Empty);
+        }
+
+        Ok(Self {
+            public: tokenize(public),
+            local: tokenize(local),
+        })
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, component) in self.public.iter().enumerate() {
+            if i > 0 {
+                write!(f, ".")?;
+            }
+            write!(f, "{component}")?;
+        }
+        if !self.local.is_empty() {
+            write!(f, "+")?;
+            for (i, component) in self.local.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ".")?;
+                }
+                write!(f, "{component}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match cmp_components(&self.public, &other.public) {
+            Ordering::Equal => match (self.local.is_empty(), other.local.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Less,
+                (false, true) => Ordering::Greater,
+                (false, false) => cmp_components(&self.local, &other.local),
+            },
+            other => other,
+        }
+    }
+}
+
+/// Compares two dot-separated component runs the way a conda version compares them: equal up to
+/// their common length, then decided by the first component one side has that the other doesn't.
+/// A trailing numeral (`1.0.1` vs `1.0`) or a trailing non-prerelease identifier (`1.0.post1` vs
+/// `1.0`) extends to a *newer* version, while a trailing prerelease marker (`1.0b1` vs `1.0`) marks
+/// a *prerelease* of the shorter version — so plain `Vec::cmp`, which always treats the shorter
+/// sequence as lesser, gets prereleases backwards.
+fn cmp_components(a: &[VersionComponent], b: &[VersionComponent]) -> Ordering {
+    for (x, y) in a.iter().zip(b.iter()) {
+        match x.cmp(y) {
+            Ordering::Equal => continue,
+            other => return other,
+        }
+    }
+    match a.len().cmp(&b.len()) {
+        Ordering::Equal => Ordering::Equal,
+        Ordering::Less => match &b[a.len()] {
+            VersionComponent::Numeral(_) => Ordering::Less,
+            VersionComponent::Ident(ident) if prerelease_rank(ident).is_some() => {
+                Ordering::Greater
+            }
+            VersionComponent::Ident(_) => Ordering::Less,
+        },
+        Ordering::Greater => match &a[b.len()] {
+            VersionComponent::Numeral(_) => Ordering::Greater,
+            VersionComponent::Ident(ident) if prerelease_rank(ident).is_some() => Ordering::Less,
+            VersionComponent::Ident(_) => Ordering::Greater,
+        },
+    }
+}
+
+/// An error that occurs when parsing a [`Version`].
+#[derive(Debug, Error, Clone, Eq, PartialEq)]
+pub enum ParseVersionError {
+    #[error("version string is empty")]
+    Empty,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Version;
+    use std::str::FromStr;
+
+    #[test]
+    fn local_version_sorts_after_plain_version() {
+        let plain = Version::from_str("1.2.3").unwrap();
+        let local = Version::from_str("1.2.3+cu118").unwrap();
+        assert!(plain < local);
+        assert_ne!(plain, local);
+    }
+
+    #[test]
+    fn local_segments_compare_numerically() {
+        let a = Version::from_str("1.0+cu11").unwrap();
+        let b = Version::from_str("1.0+cu118").unwrap();
+        assert!(a < b);
+    }
+
+    #[test]
+    fn numeral_local_segment_sorts_above_ident() {
+        let numeric = Version::from_str("1.0+1").unwrap();
+        let ident = Version::from_str("1.0+cpu").unwrap();
+        assert!(numeric > ident);
+    }
+
+    #[test]
+    fn matches_exact_ignores_local_when_unset() {
+        let constraint = Version::from_str("1.2.3").unwrap();
+        let candidate = Version::from_str("1.2.3+cu118").unwrap();
+        assert!(constraint.matches_exact(&candidate));
+
+        let pinned = Version::from_str("1.2.3+cu118").unwrap();
+        assert!(pinned.matches_exact(&candidate));
+
+        let other_build = Version::from_str("1.2.3+cpu").unwrap();
+        assert!(!pinned.matches_exact(&other_build));
+    }
+
+    #[test]
+    fn final_release_sorts_above_its_own_prerelease() {
+        let prerelease = Version::from_str("2.0b1").unwrap();
+        let release = Version::from_str("2.0").unwrap();
+        assert!(release > prerelease);
+
+        let patch = Version::from_str("1.0.1").unwrap();
+        let base = Version::from_str("1.0").unwrap();
+        assert!(patch > base);
+    }
+
+    #[test]
+    fn post_release_sorts_above_its_base_version() {
+        let base = Version::from_str("1.0").unwrap();
+        let post = Version::from_str("1.0.post1").unwrap();
+        assert!(post > base);
+    }
+
+    #[test]
+    fn prerelease_tags_rank_dev_below_alpha_below_beta_below_rc() {
+        let dev = Version::from_str("1.0.dev1").unwrap();
+        let alpha = Version::from_str("1.0a1").unwrap();
+        let beta = Version::from_str("1.0b1").unwrap();
+        let rc = Version::from_str("1.0rc1").unwrap();
+        assert!(dev < alpha);
+        assert!(alpha < beta);
+        assert!(beta < rc);
+    }
+
+    #[test]
+    fn prerelease_classification() {
+        assert!(!Version::from_str("1.0").unwrap().is_prerelease());
+        assert!(Version::from_str("1.0rc1").unwrap().is_prerelease());
+        assert!(Version::from_str("2.0b1").unwrap().is_prerelease());
+        assert!(Version::from_str("2.0.dev0").unwrap().is_prerelease());
+    }
+
+    #[test]
+    fn is_prerelease_ignores_markers_that_are_not_the_trailing_identifier() {
+        // The `dev` here is still the last *identifier*, with only its own ordinal (`2`)
+        // following it, so it's still read as a trailing `dev` marker.
+        assert!(Version::from_str("1.dev.2").unwrap().is_prerelease());
+        // But once another identifier follows the marker, it's no longer trailing.
+        assert!(!Version::from_str("1.dev.final").unwrap().is_prerelease());
+    }
+
+    #[test]
+    fn is_prerelease_matches_a_trailing_marker_with_no_ordinal() {
+        // A trailing CalVer-style letter with no further identifiers is still a marker, even
+        // without an ordinal number following it.
+        assert!(Version::from_str("2021a").unwrap().is_prerelease());
+    }
+}